@@ -38,13 +38,13 @@ impl Leds for () {}
         };
         // It would make sense to use usage_max=0xdd but boot keyboard uses 0xff. This way
         // keycodes >= KeyCode::LCtrl (notably - "unofficial media") should still work
-        // (though these only work on linux, we should use different usage page for media).
+        // (though these only work on linux; use `ConsumerReport` for cross-platform media keys).
         (usage_page = KEYBOARD, usage_min = 0x00, usage_max = 0xff) = {
             #[item_settings data,array,absolute] keycodes = input;
         };
     }
 )]
-#[derive(Default, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub struct KeyboardReport {
     /// Modifier keys packed bits
     pub modifier: u8,
@@ -58,14 +58,109 @@ pub struct KeyboardReport {
 
 const KEYBOARD_REPORT_IN_SIZE: usize = 1 + 1 + 6; // all fields besides leds
 
+/// Keyboard report using N-Key Rollover (NKRO).
+///
+/// Instead of the 6-key array from the boot protocol, every keycode in
+/// 0x00..=0xdf gets its own bit in `bitmap`, so there's no practical limit
+/// on how many keys can be reported as pressed at the same time. Boot hosts
+/// (BIOS, etc.) won't understand this report; it's only useful once the host
+/// has switched to Report protocol.
+#[gen_hid_descriptor(
+    (collection = APPLICATION, usage_page = GENERIC_DESKTOP, usage = KEYBOARD) = {
+        (usage_page = KEYBOARD, usage_min = 0xe0, usage_max = 0xe7) = {
+            #[packed_bits 8] #[item_settings data,variable,absolute] modifier = input;
+        };
+        (usage_page = LEDS, usage_min = 0x01, usage_max = 0x05) = {
+            #[packed_bits 5] #[item_settings data,variable,absolute] leds = output;
+        };
+        (usage_page = KEYBOARD, usage_min = 0x00, usage_max = 0xdf) = {
+            #[packed_bits 0xe0] #[item_settings data,variable,absolute] bitmap = input;
+        };
+    }
+)]
+#[derive(Default, Eq, PartialEq)]
+pub struct NkroKeyboardReport {
+    /// Modifier keys packed bits
+    pub modifier: u8,
+    /// LED states (host -> device)
+    pub leds: u8,
+    /// Bitmap of held keycodes: bit `k` of `bitmap[k >> 3]` set means keycode `k` is held.
+    pub bitmap: [u8; 28],
+}
+
+impl NkroKeyboardReport {
+    /// Builds an NKRO report with `modifier` and `bitmap` bit `k` set for every keycode in
+    /// `keycodes`. Keycodes outside 0x00..=0xdf (the bitmap's usage range) are ignored.
+    pub fn from_keycodes(modifier: u8, keycodes: impl IntoIterator<Item = u8>) -> Self {
+        let mut report = NkroKeyboardReport {
+            modifier,
+            ..Default::default()
+        };
+        for keycode in keycodes {
+            report.press(keycode);
+        }
+        report
+    }
+
+    /// Sets bit `k` of `bitmap[k >> 3]`, marking `keycode` as held. Ignored if `keycode` is
+    /// outside 0x00..=0xdf, the bitmap's usage range.
+    pub fn press(&mut self, keycode: u8) {
+        if let Some(byte) = self.bitmap.get_mut((keycode >> 3) as usize) {
+            *byte |= 1 << (keycode & 7);
+        }
+    }
+}
+
+const NKRO_KEYBOARD_REPORT_IN_SIZE: usize = 1 + 28; // all fields besides leds
+
+// HID class requests (HID 1.11, section 7.2), not covered by the `usb-device` crate.
+const SET_PROTOCOL: u8 = 0x0b;
+const GET_PROTOCOL: u8 = 0x03;
+const SET_IDLE: u8 = 0x0a;
+const GET_IDLE: u8 = 0x02;
+
+/// Boot/Report protocol mode, as selected by the host via SET_PROTOCOL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProtocolMode {
+    /// Boot protocol: the fixed 6-key [`KeyboardReport`] layout, used by BIOSes and KVMs.
+    Boot,
+    /// Report protocol: the full report layout declared by the device's descriptor.
+    Report,
+}
+
 /// A keyboard HID device.
 pub struct HidKeyboard<'a, B: UsbBus, L> {
     hid: HIDClass<'a, B>,
     leds: L,
+    protocol_mode: ProtocolMode,
+    /// Idle rate set by SET_IDLE, in 4 ms units. 0 means "report only on change".
+    ///
+    /// HID's SET_IDLE/GET_IDLE are actually per-report-ID (the low byte of wValue selects which
+    /// report, 0 meaning "all of them"), but `HidKeyboard` only ever has the one keyboard report
+    /// in flight, so a single device-wide rate is all there is to track; the report ID byte is
+    /// read but otherwise ignored. A device with more than one report ID sharing this idle state
+    /// (e.g. `CompositeHid`) would need to key this by report ID instead.
+    idle_rate: u8,
+    /// Time accumulated since the last report was sent, in ms.
+    idle_elapsed_ms: u16,
+    /// Last report pushed via `push_keyboard_report`, resent by `tick` once idle.
+    last_report: KeyboardReport,
+    /// Bytes of the report descriptor this device was constructed with.
+    descriptor: &'static [u8],
 }
 
 impl<'a, B: UsbBus, L> HidKeyboard<'a, B, L> {
     /// Creates a new `Keyboard` object.
+    ///
+    /// This always advertises the boot-compatible 6-key [`KeyboardReport`] descriptor, and
+    /// [`push_keyboard_report`](Self::push_keyboard_report) always sends that layout, regardless
+    /// of which protocol the host selects via SET_PROTOCOL: a report descriptor is fixed at
+    /// enumeration time, so there's no way for one device built this way to serve a different,
+    /// richer layout once the host has switched to Report protocol. [`protocol()`](Self::protocol)
+    /// still reflects the host's choice so callers can act on it, but getting an actual richer
+    /// Report-protocol layout (NKRO, consumer control, ...) means building the device from
+    /// [`new_nkro`](Self::new_nkro) or [`new_with_descriptor`](Self::new_with_descriptor) instead,
+    /// which force Report protocol from the start rather than switching into it at runtime.
     pub fn new(bus: &'a UsbBusAllocator<B>, leds: L) -> HidKeyboard<'a, B, L> {
         use usbd_hid::hid_class::*;
         let settings = HidClassSettings {
@@ -74,14 +169,61 @@ impl<'a, B: UsbBus, L> HidKeyboard<'a, B, L> {
             config: ProtocolModeConfig::ForceBoot,
             locale: HidCountryCode::NotSupported,
         };
-        let hid = HIDClass::new_ep_in_with_settings(bus, KeyboardReport::desc(), 10, settings);
+        let descriptor = KeyboardReport::desc();
+        let hid = HIDClass::new_ep_in_with_settings(bus, descriptor, 10, settings);
         HidKeyboard {
             hid,
             leds,
+            protocol_mode: ProtocolMode::Boot,
+            idle_rate: 0,
+            idle_elapsed_ms: 0,
+            last_report: KeyboardReport::default(),
+            descriptor,
         }
     }
 
+    /// Creates a new `HidKeyboard` advertising a user-supplied report descriptor instead of the
+    /// boot-compatible one from [`HidKeyboard::new`].
+    ///
+    /// This is for advanced users shipping a hand-tuned descriptor (e.g. NKRO plus consumer
+    /// control plus mouse, combined in a way this crate doesn't build for them) without having
+    /// to fork the crate. The device still reports via `push_keyboard_report`/
+    /// `push_nkro_keyboard_report`, so the descriptor's report layout must match one of those.
+    pub fn new_with_descriptor(
+        bus: &'a UsbBusAllocator<B>,
+        leds: L,
+        descriptor: &'static [u8],
+    ) -> HidKeyboard<'a, B, L> {
+        use usbd_hid::hid_class::*;
+        let settings = HidClassSettings {
+            subclass: HidSubClass::NoSubClass,
+            protocol: HidProtocol::Keyboard,
+            config: ProtocolModeConfig::ForceReport,
+            locale: HidCountryCode::NotSupported,
+        };
+        let hid = HIDClass::new_ep_in_with_settings(bus, descriptor, 30, settings);
+        HidKeyboard {
+            hid,
+            leds,
+            protocol_mode: ProtocolMode::Report,
+            idle_rate: 0,
+            idle_elapsed_ms: 0,
+            last_report: KeyboardReport::default(),
+            descriptor,
+        }
+    }
+
+    /// Returns the raw HID report descriptor bytes this device was constructed with, e.g. for
+    /// introspection or validating with an external descriptor parser before flashing.
+    pub fn report_descriptor(&self) -> &'static [u8] {
+        self.descriptor
+    }
+
     /// Push keyboard report to endpoint.
+    ///
+    /// This always sends the boot 6-key layout, whatever [`protocol()`](Self::protocol) currently
+    /// reports; see the note on [`HidKeyboard::new`] for why a single device can't switch to a
+    /// different report layout at runtime.
     pub fn push_keyboard_report(&mut self, report: &KeyboardReport) -> usb_device::Result<()> {
         self.hid.push_input(report)
             .and_then(|bytes_written| {
@@ -90,11 +232,92 @@ impl<'a, B: UsbBus, L> HidKeyboard<'a, B, L> {
                 if bytes_written != KEYBOARD_REPORT_IN_SIZE {
                     Err(usb_device::UsbError::BufferOverflow)
                 } else {
+                    self.last_report = *report;
+                    self.idle_elapsed_ms = 0;
                     Ok(())
                 }
             })
     }
 
+    /// Advances the idle-rate timer by `elapsed_ms` milliseconds, resending the last keyboard
+    /// report once the idle interval (set by the host via SET_IDLE) elapses.
+    ///
+    /// Call this periodically, e.g. from a millisecond timer tick. An idle rate of 0 means
+    /// "report only on change", so this is then a no-op.
+    pub fn tick(&mut self, elapsed_ms: u16) {
+        if self.idle_rate == 0 {
+            return;
+        }
+        self.idle_elapsed_ms = self.idle_elapsed_ms.saturating_add(elapsed_ms);
+        let interval_ms = self.idle_rate as u16 * 4;
+        if self.idle_elapsed_ms >= interval_ms {
+            // Don't reset idle_elapsed_ms here: if the endpoint isn't ready yet (WouldBlock) or
+            // push_keyboard_report otherwise fails, leaving it past the threshold means the very
+            // next tick() retries the resend instead of silently waiting out another full idle
+            // interval. push_keyboard_report resets it to 0 itself once the push actually succeeds.
+            let report = self.last_report;
+            let _ = self.push_keyboard_report(&report);
+        }
+    }
+
+    /// Creates a new `HidKeyboard` advertising an NKRO report descriptor instead of the
+    /// boot-compatible 6-key one.
+    ///
+    /// This forces Report protocol (`ProtocolModeConfig::ForceReport`): boot hosts need the
+    /// 6-key path from [`HidKeyboard::new`] instead, since they can't parse the NKRO bitmap.
+    pub fn new_nkro(bus: &'a UsbBusAllocator<B>, leds: L) -> HidKeyboard<'a, B, L> {
+        use usbd_hid::hid_class::*;
+        let settings = HidClassSettings {
+            subclass: HidSubClass::NoSubClass,
+            protocol: HidProtocol::Keyboard,
+            config: ProtocolModeConfig::ForceReport,
+            locale: HidCountryCode::NotSupported,
+        };
+        let descriptor = NkroKeyboardReport::desc();
+        let hid = HIDClass::new_ep_in_with_settings(bus, descriptor, 30, settings);
+        HidKeyboard {
+            hid,
+            leds,
+            protocol_mode: ProtocolMode::Report,
+            idle_rate: 0,
+            idle_elapsed_ms: 0,
+            last_report: KeyboardReport::default(),
+            descriptor,
+        }
+    }
+
+    /// Push an NKRO keyboard report to the endpoint.
+    ///
+    /// This is only meaningful once the host has selected [`ProtocolMode::Report`] (see
+    /// [`HidKeyboard::protocol`]); boot hosts expect the fixed 6-key boot report and can't
+    /// parse the NKRO bitmap, so this returns `WouldBlock` while still in `ProtocolMode::Boot`.
+    pub fn push_nkro_keyboard_report(&mut self, report: &NkroKeyboardReport) -> usb_device::Result<()> {
+        if self.protocol_mode == ProtocolMode::Boot {
+            return Err(usb_device::UsbError::WouldBlock);
+        }
+        self.hid.push_input(report)
+            .and_then(|bytes_written| {
+                // If bytes_written is different than report size then this means that the allocated
+                // endpoint size is too small, which should be a panic!
+                if bytes_written != NKRO_KEYBOARD_REPORT_IN_SIZE {
+                    Err(usb_device::UsbError::BufferOverflow)
+                } else {
+                    Ok(())
+                }
+            })
+    }
+
+    /// Returns the currently selected Boot/Report protocol mode.
+    pub fn protocol(&self) -> ProtocolMode {
+        self.protocol_mode
+    }
+
+    /// Returns the idle rate set by the host via SET_IDLE, in 4 ms units (0 meaning
+    /// "report only on change").
+    pub fn idle_rate(&self) -> u8 {
+        self.idle_rate
+    }
+
     /// Returns the underlying leds object.
     pub fn leds_mut(&mut self) -> &mut L {
         &mut self.leds
@@ -103,8 +326,40 @@ impl<'a, B: UsbBus, L> HidKeyboard<'a, B, L> {
 
 
 impl<B: UsbBus, L: Leds> UsbClass<B> for HidKeyboard<'_, B, L> {
-    // Call appropriate methods from Leds on set_report request.
+    // Handle SET_PROTOCOL/SET_IDLE ourselves, call appropriate methods from Leds on set_report request.
     fn control_out(&mut self, xfer: usb_device::class_prelude::ControlOut<B>) {
+        let request = xfer.request();
+        // wIndex is the target interface for an Interface-recipient request; in a composite
+        // device with more than one interface (e.g. alongside a `CompositeHid`), every other
+        // class's control_out is also called for this same transfer, so we must only answer
+        // requests actually addressed to our own interface.
+        let for_this_interface = request.recipient == usb_device::control::Recipient::Interface
+            && request.index == u8::from(self.hid.interface_number()) as u16;
+        if for_this_interface
+            && request.request_type == usb_device::control::RequestType::Class
+            && request.request == SET_PROTOCOL
+        {
+            self.protocol_mode = if request.value == 0 {
+                ProtocolMode::Boot
+            } else {
+                ProtocolMode::Report
+            };
+            xfer.accept().ok();
+            return;
+        }
+        if for_this_interface
+            && request.request_type == usb_device::control::RequestType::Class
+            && request.request == SET_IDLE
+        {
+            // wValue: high byte is the idle duration in 4 ms units, low byte is the report ID.
+            // The report ID is ignored: see the note on `idle_rate` for why a single device-wide
+            // rate is correct for this single-report device.
+            self.idle_rate = (request.value >> 8) as u8;
+            self.idle_elapsed_ms = 0;
+            xfer.accept().ok();
+            return;
+        }
+
         self.hid.control_out(xfer);
 
         let mut leds = 0u8;
@@ -142,6 +397,430 @@ impl<B: UsbBus, L: Leds> UsbClass<B> for HidKeyboard<'_, B, L> {
         self.hid.poll()
     }
 
+    fn control_in(&mut self, xfer: usb_device::class_prelude::ControlIn<B>) {
+        let request = xfer.request();
+        // See the matching comment in `control_out`: only answer requests addressed to our own
+        // interface, since every class's control_in is called for every control transfer.
+        let for_this_interface = request.recipient == usb_device::control::Recipient::Interface
+            && request.index == u8::from(self.hid.interface_number()) as u16;
+        if for_this_interface
+            && request.request_type == usb_device::control::RequestType::Class
+            && request.request == GET_PROTOCOL
+        {
+            let value = match self.protocol_mode {
+                ProtocolMode::Boot => 0u8,
+                ProtocolMode::Report => 1u8,
+            };
+            xfer.accept_with(&[value]).ok();
+            return;
+        }
+        if for_this_interface
+            && request.request_type == usb_device::control::RequestType::Class
+            && request.request == GET_IDLE
+        {
+            // wValue's report ID low byte is ignored here too; see the note on `idle_rate`.
+            xfer.accept_with(&[self.idle_rate]).ok();
+            return;
+        }
+
+        // Everything else, including standard GET_DESCRIPTOR for the HID Report descriptor on
+        // this interface (request_type == Standard, so it never matches the Class/Interface
+        // checks above), is answered by the underlying `HIDClass`.
+        self.hid.control_in(xfer)
+    }
+
+    fn endpoint_setup(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_setup(addr)
+    }
+
+    fn endpoint_out(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_out(addr)
+    }
+
+    fn endpoint_in_complete(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_in_complete(addr)
+    }
+}
+
+/// Keyboard collection of [`CompositeHid`], carried under Report ID 1.
+#[gen_hid_descriptor(
+    (collection = APPLICATION, usage_page = GENERIC_DESKTOP, usage = KEYBOARD, report_id = 0x01) = {
+        (usage_page = KEYBOARD, usage_min = 0xe0, usage_max = 0xe7) = {
+            #[packed_bits 8] #[item_settings data,variable,absolute] modifier = input;
+        };
+        (usage_min = 0x00, usage_max = 0xff) = {
+            #[item_settings constant,variable,absolute] reserved=input;
+        };
+        (usage_page = LEDS, usage_min = 0x01, usage_max = 0x05) = {
+            #[packed_bits 5] #[item_settings data,variable,absolute] leds = output;
+        };
+        (usage_page = KEYBOARD, usage_min = 0x00, usage_max = 0xff) = {
+            #[item_settings data,array,absolute] keycodes = input;
+        };
+    }
+)]
+#[derive(Default, Eq, PartialEq)]
+pub struct CompositeKeyboardReport {
+    /// Modifier keys packed bits
+    pub modifier: u8,
+    /// Boot keyboard reserved field
+    pub reserved: u8,
+    /// LED states (host -> device)
+    pub leds: u8,
+    /// Boot keyboard keycodes list
+    pub keycodes: [u8; 6],
+}
+
+/// Relative mouse collection of [`CompositeHid`], carried under Report ID 2.
+#[gen_hid_descriptor(
+    (collection = APPLICATION, usage_page = GENERIC_DESKTOP, usage = MOUSE, report_id = 0x02) = {
+        (collection = PHYSICAL, usage = POINTER) = {
+            // No explicit report_count/report_size here: packed_bits alone drives both, the
+            // same way the boot keyboard descriptor's LED field does, which is what makes the
+            // macro pad the remaining 3 bits out to a full byte before the axes below.
+            (usage_page = BUTTON, usage_min = 1, usage_max = 5) = {
+                #[packed_bits 5] #[item_settings data,variable,absolute] buttons = input;
+            };
+            (usage_page = GENERIC_DESKTOP, logical_minimum = -127, logical_maximum = 127) = {
+                (usage = 0x30,) = {
+                    #[item_settings data,variable,relative] x = input;
+                };
+                (usage = 0x31,) = {
+                    #[item_settings data,variable,relative] y = input;
+                };
+                (usage = 0x38,) = {
+                    #[item_settings data,variable,relative] wheel = input;
+                };
+            };
+        };
+    }
+)]
+#[derive(Default, Eq, PartialEq)]
+pub struct CompositeMouseReport {
+    /// Mouse button packed bits (buttons 1-5; upper 3 bits are padding)
+    pub buttons: u8,
+    /// Relative X movement
+    pub x: i8,
+    /// Relative Y movement
+    pub y: i8,
+    /// Relative wheel movement
+    pub wheel: i8,
+}
+
+/// Consumer control collection of [`CompositeHid`], carried under Report ID 3.
+///
+/// `gen_hid_descriptor` doesn't recognize `CONSUMER`/`CONSUMER_CONTROL` as named usage page/usage
+/// constants, so these are spelled out numerically (usage page 0x0c, "Consumer Control" usage 0x01).
+#[gen_hid_descriptor(
+    (collection = APPLICATION, usage_page = 0x0c, usage = 0x01, report_id = 0x03) = {
+        (usage_page = 0x0c, logical_minimum = 0x00, logical_maximum = 0x3ff, usage_min = 0x00, usage_max = 0x3ff) = {
+            #[item_settings data,array,absolute] usage = input;
+        };
+    }
+)]
+#[derive(Default, Eq, PartialEq)]
+pub struct CompositeConsumerReport {
+    /// Active consumer control usage (e.g. Play/Pause, Vol+, Vol-, Mute).
+    pub usage: u16,
+}
+
+// `SerializedDescriptor::desc()` is an ordinary (non-const) fn, so it can't be called from a
+// const context to concatenate `CompositeKeyboardReport`/`CompositeMouseReport`/
+// `CompositeConsumerReport`'s descriptors into one at compile time. Each `#[gen_hid_descriptor]`
+// invocation above still emits one complete, self-terminated Application collection tagged with
+// its own Report ID though, so hand-writing their concatenation below is equivalent to what that
+// const-eval would have produced; it must just be kept in sync with the three structs above by
+// hand. This is exactly the kind of hand-tuned descriptor `HidKeyboard::new_with_descriptor`
+// exists for.
+#[rustfmt::skip]
+const COMPOSITE_REPORT_DESCRIPTOR: &[u8] = &[
+    // --- Keyboard collection (Report ID 1), matching CompositeKeyboardReport ---
+    0x05, 0x01,       // Usage Page (Generic Desktop)
+    0x09, 0x06,       // Usage (Keyboard)
+    0xA1, 0x01,       // Collection (Application)
+    0x85, 0x01,       //   Report ID (1)
+    0x05, 0x07,       //   Usage Page (Key Codes)
+    0x19, 0xE0,       //   Usage Minimum (224)
+    0x29, 0xE7,       //   Usage Maximum (231)
+    0x15, 0x00,       //   Logical Minimum (0)
+    0x25, 0x01,       //   Logical Maximum (1)
+    0x75, 0x01,       //   Report Size (1)
+    0x95, 0x08,       //   Report Count (8)
+    0x81, 0x02,       //   Input (Data, Variable, Absolute) -- modifier
+    0x95, 0x01,       //   Report Count (1)
+    0x75, 0x08,       //   Report Size (8)
+    0x81, 0x01,       //   Input (Constant) -- reserved
+    0x95, 0x05,       //   Report Count (5)
+    0x75, 0x01,       //   Report Size (1)
+    0x05, 0x08,       //   Usage Page (LEDs)
+    0x19, 0x01,       //   Usage Minimum (1)
+    0x29, 0x05,       //   Usage Maximum (5)
+    0x91, 0x02,       //   Output (Data, Variable, Absolute) -- leds
+    0x95, 0x01,       //   Report Count (1)
+    0x75, 0x03,       //   Report Size (3)
+    0x91, 0x01,       //   Output (Constant) -- leds padding
+    0x95, 0x06,       //   Report Count (6)
+    0x75, 0x08,       //   Report Size (8)
+    0x15, 0x00,       //   Logical Minimum (0)
+    0x26, 0xFF, 0x00, //   Logical Maximum (255)
+    0x05, 0x07,       //   Usage Page (Key Codes)
+    0x19, 0x00,       //   Usage Minimum (0)
+    0x29, 0xFF,       //   Usage Maximum (255)
+    0x81, 0x00,       //   Input (Data, Array) -- keycodes
+    0xC0,             // End Collection
+
+    // --- Relative mouse collection (Report ID 2), matching CompositeMouseReport ---
+    0x05, 0x01,       // Usage Page (Generic Desktop)
+    0x09, 0x02,       // Usage (Mouse)
+    0xA1, 0x01,       // Collection (Application)
+    0x85, 0x02,       //   Report ID (2)
+    0x09, 0x01,       //   Usage (Pointer)
+    0xA1, 0x00,       //   Collection (Physical)
+    0x05, 0x09,       //     Usage Page (Button)
+    0x19, 0x01,       //     Usage Minimum (Button 1)
+    0x29, 0x05,       //     Usage Maximum (Button 5)
+    0x15, 0x00,       //     Logical Minimum (0)
+    0x25, 0x01,       //     Logical Maximum (1)
+    0x95, 0x05,       //     Report Count (5)
+    0x75, 0x01,       //     Report Size (1)
+    0x81, 0x02,       //     Input (Data, Variable, Absolute) -- buttons
+    0x95, 0x01,       //     Report Count (1)
+    0x75, 0x03,       //     Report Size (3)
+    0x81, 0x01,       //     Input (Constant) -- buttons padding
+    0x05, 0x01,       //     Usage Page (Generic Desktop)
+    0x15, 0x81,       //     Logical Minimum (-127)
+    0x25, 0x7F,       //     Logical Maximum (127)
+    0x75, 0x08,       //     Report Size (8)
+    0x95, 0x01,       //     Report Count (1)
+    0x09, 0x30,       //     Usage (X)
+    0x81, 0x06,       //     Input (Data, Variable, Relative) -- x
+    0x09, 0x31,       //     Usage (Y)
+    0x81, 0x06,       //     Input (Data, Variable, Relative) -- y
+    0x09, 0x38,       //     Usage (Wheel)
+    0x81, 0x06,       //     Input (Data, Variable, Relative) -- wheel
+    0xC0,             //   End Collection
+    0xC0,             // End Collection
+
+    // --- Consumer control collection (Report ID 3), matching CompositeConsumerReport ---
+    0x05, 0x0C,       // Usage Page (Consumer)
+    0x09, 0x01,       // Usage (Consumer Control)
+    0xA1, 0x01,       // Collection (Application)
+    0x85, 0x03,       //   Report ID (3)
+    0x15, 0x00,       //   Logical Minimum (0)
+    0x26, 0xFF, 0x03, //   Logical Maximum (1023)
+    0x19, 0x00,       //   Usage Minimum (0)
+    0x2A, 0xFF, 0x03, //   Usage Maximum (1023)
+    0x75, 0x10,       //   Report Size (16)
+    0x95, 0x01,       //   Report Count (1)
+    0x81, 0x00,       //   Input (Data, Array, Absolute) -- usage
+    0xC0,             // End Collection
+];
+
+const COMPOSITE_KEYBOARD_REPORT_IN_SIZE: usize = 1 + 1 + 6; // modifier + reserved + keycodes (leds is host->device only)
+const COMPOSITE_MOUSE_REPORT_IN_SIZE: usize = 1 + 1 + 1 + 1; // buttons + x + y + wheel
+const COMPOSITE_CONSUMER_REPORT_IN_SIZE: usize = 2; // usage
+
+/// A composite HID device bundling a keyboard, a relative mouse and a consumer-control
+/// collection behind a single interface, distinguished by Report ID (1, 2 and 3 respectively).
+///
+/// This is the common single-interface composite pattern: one report descriptor carrying
+/// multiple top-level collections, so media keys and a pointer can be exposed without the
+/// user hand-rolling a second `UsbClass`.
+pub struct CompositeHid<'a, B: UsbBus> {
+    hid: HIDClass<'a, B>,
+}
+
+impl<'a, B: UsbBus> CompositeHid<'a, B> {
+    /// Creates a new `CompositeHid` object.
+    pub fn new(bus: &'a UsbBusAllocator<B>) -> CompositeHid<'a, B> {
+        use usbd_hid::hid_class::*;
+        // COMPOSITE_REPORT_DESCRIPTOR is hand-written (see its definition) rather than derived
+        // from these structs' own `desc()`, so it can silently drift out of sync with them; this
+        // compares every byte, not just the total length, so an edit to one of the three structs
+        // that wasn't mirrored into the byte array is caught even if it happens to preserve length.
+        debug_assert!(
+            COMPOSITE_REPORT_DESCRIPTOR.iter().copied().eq(CompositeKeyboardReport::desc()
+                .iter()
+                .copied()
+                .chain(CompositeMouseReport::desc().iter().copied())
+                .chain(CompositeConsumerReport::desc().iter().copied())),
+            "COMPOSITE_REPORT_DESCRIPTOR is out of sync with the report structs it describes",
+        );
+        let settings = HidClassSettings {
+            subclass: HidSubClass::NoSubClass,
+            protocol: HidProtocol::Keyboard,
+            config: ProtocolModeConfig::ForceReport,
+            locale: HidCountryCode::NotSupported,
+        };
+        let hid = HIDClass::new_ep_in_with_settings(bus, COMPOSITE_REPORT_DESCRIPTOR, 16, settings);
+        CompositeHid { hid }
+    }
+
+    /// Push a keyboard report (Report ID 1).
+    pub fn push_keyboard(&mut self, report: &CompositeKeyboardReport) -> usb_device::Result<()> {
+        self.hid.push_input(report).and_then(|bytes_written| {
+            // If bytes_written is different than report size then this means that the allocated
+            // endpoint size is too small, which should be a panic!
+            if bytes_written != COMPOSITE_KEYBOARD_REPORT_IN_SIZE {
+                Err(usb_device::UsbError::BufferOverflow)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Push a mouse report (Report ID 2).
+    pub fn push_mouse(&mut self, report: &CompositeMouseReport) -> usb_device::Result<()> {
+        self.hid.push_input(report).and_then(|bytes_written| {
+            // If bytes_written is different than report size then this means that the allocated
+            // endpoint size is too small, which should be a panic!
+            if bytes_written != COMPOSITE_MOUSE_REPORT_IN_SIZE {
+                Err(usb_device::UsbError::BufferOverflow)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Push a consumer-control report (Report ID 3).
+    pub fn push_consumer(&mut self, report: &CompositeConsumerReport) -> usb_device::Result<()> {
+        self.hid.push_input(report).and_then(|bytes_written| {
+            // If bytes_written is different than report size then this means that the allocated
+            // endpoint size is too small, which should be a panic!
+            if bytes_written != COMPOSITE_CONSUMER_REPORT_IN_SIZE {
+                Err(usb_device::UsbError::BufferOverflow)
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for CompositeHid<'_, B> {
+    fn get_configuration_descriptors(&self, writer: &mut usb_device::descriptor::DescriptorWriter) -> usb_device::Result<()> {
+        self.hid.get_configuration_descriptors(writer)
+    }
+
+    fn get_bos_descriptors(&self, writer: &mut usb_device::descriptor::BosWriter) -> usb_device::Result<()> {
+        self.hid.get_bos_descriptors(writer)
+    }
+
+    fn get_string(&self, index: usb_device::class_prelude::StringIndex, lang_id: u16) -> Option<&str> {
+        self.hid.get_string(index, lang_id)
+    }
+
+    fn reset(&mut self) {
+        self.hid.reset()
+    }
+
+    fn poll(&mut self) {
+        self.hid.poll()
+    }
+
+    fn control_out(&mut self, xfer: usb_device::class_prelude::ControlOut<B>) {
+        self.hid.control_out(xfer)
+    }
+
+    fn control_in(&mut self, xfer: usb_device::class_prelude::ControlIn<B>) {
+        self.hid.control_in(xfer)
+    }
+
+    fn endpoint_setup(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_setup(addr)
+    }
+
+    fn endpoint_out(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_out(addr)
+    }
+
+    fn endpoint_in_complete(&mut self, addr: usb_device::endpoint::EndpointAddress) {
+        self.hid.endpoint_in_complete(addr)
+    }
+}
+
+/// Standalone Consumer Control report for media keys (usage page 0x0C).
+///
+/// Unlike the "unofficial media" trick on the keyboard usage page (see the comment on
+/// [`KeyboardReport`]), this works on Windows and macOS too, not just Linux. See
+/// [`CompositeConsumerReport`] for the same collection bundled into [`CompositeHid`].
+// gen_hid_descriptor doesn't recognize CONSUMER/CONSUMER_CONTROL as named usage page/usage
+// constants (see CompositeConsumerReport), so these are spelled out numerically too.
+#[gen_hid_descriptor(
+    (collection = APPLICATION, usage_page = 0x0c, usage = 0x01) = {
+        (usage_page = 0x0c, logical_minimum = 0x00, logical_maximum = 0x3ff, usage_min = 0x00, usage_max = 0x3ff) = {
+            #[item_settings data,array,absolute] usage = input;
+        };
+    }
+)]
+#[derive(Default, Eq, PartialEq)]
+pub struct ConsumerReport {
+    /// Active consumer control usage, e.g. Play/Pause (0xcd), Vol+ (0xe9), Vol- (0xea),
+    /// Mute (0xe2), Next (0xb5) or Prev (0xb6).
+    pub usage: u16,
+}
+
+const CONSUMER_REPORT_IN_SIZE: usize = 2;
+
+/// A standalone consumer-control HID device, for media keys without pulling in the full
+/// `CompositeHid` keyboard+mouse bundle.
+pub struct HidConsumer<'a, B: UsbBus> {
+    hid: HIDClass<'a, B>,
+}
+
+impl<'a, B: UsbBus> HidConsumer<'a, B> {
+    /// Creates a new `HidConsumer` object.
+    pub fn new(bus: &'a UsbBusAllocator<B>) -> HidConsumer<'a, B> {
+        use usbd_hid::hid_class::*;
+        let settings = HidClassSettings {
+            subclass: HidSubClass::NoSubClass,
+            protocol: HidProtocol::Generic,
+            config: ProtocolModeConfig::ForceReport,
+            locale: HidCountryCode::NotSupported,
+        };
+        let hid = HIDClass::new_ep_in_with_settings(bus, ConsumerReport::desc(), 10, settings);
+        HidConsumer { hid }
+    }
+
+    /// Push a consumer-control report to the endpoint.
+    pub fn push_consumer_report(&mut self, report: &ConsumerReport) -> usb_device::Result<()> {
+        self.hid.push_input(report)
+            .and_then(|bytes_written| {
+                // If bytes_written is different than report size then this means that the allocated
+                // endpoint size is too small, which should be a panic!
+                if bytes_written != CONSUMER_REPORT_IN_SIZE {
+                    Err(usb_device::UsbError::BufferOverflow)
+                } else {
+                    Ok(())
+                }
+            })
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for HidConsumer<'_, B> {
+    fn get_configuration_descriptors(&self, writer: &mut usb_device::descriptor::DescriptorWriter) -> usb_device::Result<()> {
+        self.hid.get_configuration_descriptors(writer)
+    }
+
+    fn get_bos_descriptors(&self, writer: &mut usb_device::descriptor::BosWriter) -> usb_device::Result<()> {
+        self.hid.get_bos_descriptors(writer)
+    }
+
+    fn get_string(&self, index: usb_device::class_prelude::StringIndex, lang_id: u16) -> Option<&str> {
+        self.hid.get_string(index, lang_id)
+    }
+
+    fn reset(&mut self) {
+        self.hid.reset()
+    }
+
+    fn poll(&mut self) {
+        self.hid.poll()
+    }
+
+    fn control_out(&mut self, xfer: usb_device::class_prelude::ControlOut<B>) {
+        self.hid.control_out(xfer)
+    }
+
     fn control_in(&mut self, xfer: usb_device::class_prelude::ControlIn<B>) {
         self.hid.control_in(xfer)
     }